@@ -2,6 +2,7 @@
 //! entry point of the Rust logic.
 
 mod messages;
+pub mod report;
 
 use tokio;
 // use tokio_with_wasm::tokio; // Uncomment this line to target the web
@@ -15,6 +16,10 @@ rinf::write_interface!();
 // use `tokio::task::spawn_blocking`.
 async fn main() {
     use messages::basic::*;
+    // Enable the `panic-reporter` feature to report panics to Dart.
+    #[cfg(feature = "panic-reporter")]
+    report::install_panic_reporter();
+    // Report errors to Dart with `report::report_error`.
     // Send signals to Dart like below.
     SmallNumber { number: 7 }.send_signal_to_dart();
     // Get receivers that listen to Dart signals like below.
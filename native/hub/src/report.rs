@@ -0,0 +1,131 @@
+//! Reporting of Rust-side failures to Dart.
+
+use crate::messages::report::{AppError, RustPanic};
+use crate::tokio;
+use crate::tokio::sync::mpsc;
+use std::cell::Cell;
+use std::panic::catch_unwind;
+
+thread_local! {
+    // Set while the reporting task sends a panic to Dart.
+    static IS_REPORTING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Sends an `AppError` signal to Dart
+/// so that the UI can react to a failure in Rust.
+pub fn report_error(category: impl Into<String>, message: impl Into<String>, detail: Option<&str>) {
+    app_error(category, message, detail).send_signal_to_dart();
+}
+
+fn app_error(
+    category: impl Into<String>,
+    message: impl Into<String>,
+    detail: Option<&str>,
+) -> AppError {
+    AppError {
+        category: category.into(),
+        message: message.into(),
+        detail: detail.map(str::to_owned),
+    }
+}
+
+/// Installs a panic hook that reports panics to Dart
+/// as `RustPanic` signals.
+///
+/// It has to be called from within the Tokio runtime,
+/// because it spawns the task that sends the signals.
+/// See `install_panic_reporter_with` for what the hook guarantees.
+pub fn install_panic_reporter() {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<(String, String)>();
+    tokio::spawn(async move {
+        while let Some((message, location)) = receiver.recv().await {
+            // Sending can panic, for example if Dart has not prepared
+            // its isolate yet. Such a panic is caught here
+            // and is not reported again.
+            IS_REPORTING_PANIC.with(|flag| flag.set(true));
+            let _ = catch_unwind(|| RustPanic { message, location }.send_signal_to_dart());
+            IS_REPORTING_PANIC.with(|flag| flag.set(false));
+        }
+    });
+    install_panic_reporter_with(move |message, location| {
+        // An unbounded send never blocks,
+        // and it only fails after the reporting task has stopped.
+        let _ = sender.send((message, location));
+    });
+}
+
+/// Installs a panic hook that passes the message and location
+/// of each panic to `sink`, then runs the previously installed hook.
+///
+/// The hook does not lock anything or send signals itself,
+/// so it cannot block or panic as long as `sink` does neither.
+/// Panics that happen while a panic is being sent to Dart
+/// are not passed to `sink`, but the previous hook still runs for them.
+pub fn install_panic_reporter_with(sink: impl Fn(String, String) + Send + Sync + 'static) {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        if !IS_REPORTING_PANIC.with(|flag| flag.get()) {
+            let payload = panic_info.payload();
+            let message = if let Some(text) = payload.downcast_ref::<&str>() {
+                text.to_string()
+            } else if let Some(text) = payload.downcast_ref::<String>() {
+                text.clone()
+            } else {
+                String::from("Box<dyn Any>")
+            };
+            let location = panic_info
+                .location()
+                .map(|location| location.to_string())
+                .unwrap_or_default();
+            sink(message, location);
+        }
+        previous_hook(panic_info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_error_maps_fields() {
+        let error = app_error("io", String::from("file not found"), Some("config.json"));
+        assert_eq!(error.category, "io");
+        assert_eq!(error.message, "file not found");
+        assert_eq!(error.detail.as_deref(), Some("config.json"));
+
+        let error = app_error("network", "timed out", None);
+        assert_eq!(error.category, "network");
+        assert_eq!(error.message, "timed out");
+        assert_eq!(error.detail, None);
+    }
+
+    #[test]
+    fn panic_reporter_passes_message_and_location() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        install_panic_reporter_with(move |message, location| {
+            let _ = sender.send((message, location));
+        });
+
+        let _ = std::thread::spawn(|| panic!("static message")).join();
+        let (message, location) = receiver.recv().unwrap();
+        assert_eq!(message, "static message");
+        assert_location(&location);
+
+        let code = 7;
+        let _ = std::thread::spawn(move || panic!("formatted message {}", code)).join();
+        let (message, location) = receiver.recv().unwrap();
+        assert_eq!(message, "formatted message 7");
+        assert_location(&location);
+    }
+
+    fn assert_location(location: &str) {
+        let mut parts = location.rsplitn(3, ':');
+        let column = parts.next().unwrap();
+        let line = parts.next().unwrap();
+        let file = parts.next().unwrap();
+        assert!(column.parse::<u32>().is_ok());
+        assert!(line.parse::<u32>().is_ok());
+        assert!(file.ends_with("report.rs"));
+    }
+}